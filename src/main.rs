@@ -1,12 +1,14 @@
 use clap::Parser;
 use std::error::Error;
 use std::fmt;
+use std::io::{self, Read};
 use uom::fmt::DisplayStyle::Abbreviation;
 use uom::si::energy::{electronvolt, gigaelectronvolt, joule, kiloelectronvolt, megaelectronvolt};
 use uom::si::f32::*;
-use uom::si::length::{centimeter, kilometer, meter};
+use uom::si::length::{angstrom, meter, nanometer};
 use uom::si::mass::kilogram;
-use uom::si::time::{microsecond, millisecond, nanosecond, second};
+use uom::si::time::second;
+use uom::si::velocity::{kilometer_per_second, meter_per_second};
 
 // Define custom error type for unsupported units
 #[derive(Debug)]
@@ -14,6 +16,8 @@ enum UnsupportedUnitError {
     Length(String),
     Time(String),
     Energy(String),
+    Velocity(String),
+    Wavelength(String),
 }
 
 impl fmt::Display for UnsupportedUnitError {
@@ -22,6 +26,12 @@ impl fmt::Display for UnsupportedUnitError {
             UnsupportedUnitError::Length(unit) => write!(f, "Unsupported length unit: {}", unit),
             UnsupportedUnitError::Time(unit) => write!(f, "Unsupported time unit: {}", unit),
             UnsupportedUnitError::Energy(unit) => write!(f, "Unsupported energy unit: {}", unit),
+            UnsupportedUnitError::Velocity(unit) => {
+                write!(f, "Unsupported velocity unit: {}", unit)
+            }
+            UnsupportedUnitError::Wavelength(unit) => {
+                write!(f, "Unsupported wavelength unit: {}", unit)
+            }
         }
     }
 }
@@ -32,6 +42,8 @@ impl Error for UnsupportedUnitError {}
 enum DivideByZeroError {
     LengthIsZero,
     TimeIsZero,
+    EnergyIsZero,
+    FasterThanLight,
 }
 
 impl fmt::Display for DivideByZeroError {
@@ -39,12 +51,153 @@ impl fmt::Display for DivideByZeroError {
         match self {
             DivideByZeroError::LengthIsZero => write!(f, "Length is zero, cannot divide by zero"),
             DivideByZeroError::TimeIsZero => write!(f, "Time is zero, cannot divide by zero"),
+            DivideByZeroError::EnergyIsZero => write!(f, "Energy is zero, cannot divide by zero"),
+            DivideByZeroError::FasterThanLight => {
+                write!(f, "Flight velocity is at or above the speed of light")
+            }
         }
     }
 }
 
 impl std::error::Error for DivideByZeroError {}
 
+// Error returned while parsing a single-token quantity such as "250ns".
+// Each variant carries the byte offset in the input where parsing failed,
+// mirroring humantime's `Error::{InvalidCharacter, NumberExpected, UnknownUnit}`.
+#[derive(Debug)]
+enum QuantityParseError {
+    InvalidCharacter(usize),
+    NumberExpected(usize),
+    UnknownUnit { offset: usize, unit: String },
+}
+
+impl fmt::Display for QuantityParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuantityParseError::InvalidCharacter(offset) => {
+                write!(f, "invalid character at offset {}", offset)
+            }
+            QuantityParseError::NumberExpected(offset) => {
+                write!(f, "expected a number at offset {}", offset)
+            }
+            QuantityParseError::UnknownUnit { offset, unit } => {
+                write!(f, "unknown unit \"{}\" at offset {}", unit, offset)
+            }
+        }
+    }
+}
+
+impl Error for QuantityParseError {}
+
+// A numeric value paired with its (still textual) unit suffix, parsed from a
+// single token like "250ns", "10.5cm", or "3 km". The unit is dispatched to
+// the existing per-dimension unit tables (`parse_length`/`parse_time`/...)
+// by the caller, which knows the expected dimension.
+struct Quantity {
+    value: f32,
+    unit: String,
+    // Byte offset where the unit suffix begins, used to position unit errors.
+    unit_offset: usize,
+}
+
+impl std::str::FromStr for Quantity {
+    type Err = QuantityParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Scan the leading run of number characters (digits, a single `.`, and
+        // an optional leading sign). A second `.` or a misplaced sign is a
+        // malformed number, reported at the offset of the offending character.
+        let mut end = 0;
+        let mut seen_dot = false;
+        let mut seen_digit = false;
+        for (i, c) in s.char_indices() {
+            if c.is_ascii_digit() {
+                seen_digit = true;
+                end = i + c.len_utf8();
+            } else if c == '.' {
+                if seen_dot {
+                    return Err(QuantityParseError::InvalidCharacter(i));
+                }
+                seen_dot = true;
+                end = i + c.len_utf8();
+            } else if c == '+' || c == '-' {
+                if i != 0 {
+                    return Err(QuantityParseError::InvalidCharacter(i));
+                }
+                end = i + c.len_utf8();
+            } else {
+                break;
+            }
+        }
+
+        if !seen_digit {
+            return Err(QuantityParseError::NumberExpected(0));
+        }
+
+        let value: f32 = s[..end]
+            .parse()
+            .map_err(|_| QuantityParseError::InvalidCharacter(end.saturating_sub(1)))?;
+
+        let unit = s[end..].trim().to_string();
+        if unit.is_empty() {
+            return Err(QuantityParseError::UnknownUnit {
+                offset: end,
+                unit,
+            });
+        }
+
+        Ok(Quantity {
+            value,
+            unit,
+            unit_offset: end,
+        })
+    }
+}
+
+impl Quantity {
+    // Build a quantity from clap's 1-or-2 token argument list, keeping the old
+    // two-token form (`--length 10 cm`) working alongside the single-token one.
+    fn from_args(args: &[String]) -> Result<Quantity, QuantityParseError> {
+        match args {
+            [single] => single.parse(),
+            [value, unit] => {
+                let value: f32 = value
+                    .parse()
+                    .map_err(|_| QuantityParseError::NumberExpected(0))?;
+                Ok(Quantity {
+                    value,
+                    unit: unit.trim().to_string(),
+                    unit_offset: 0,
+                })
+            }
+            _ => unreachable!("clap enforces num_args(1..=2)"),
+        }
+    }
+
+    // Resolve as a length, positioning an unknown unit at its byte offset.
+    fn length(&self) -> Result<Length, QuantityParseError> {
+        parse_length(self.value, self.unit.trim()).map_err(|_| self.unknown_unit())
+    }
+
+    // Resolve as a time, positioning an unknown unit at its byte offset.
+    fn time(&self) -> Result<Time, QuantityParseError> {
+        parse_time(self.value, self.unit.trim()).map_err(|_| self.unknown_unit())
+    }
+
+    // Resolve as an energy, positioning an unknown unit at its byte offset.
+    fn energy(&self) -> Result<Energy, QuantityParseError> {
+        let unit = parse_energy_unit(self.unit.trim()).map_err(|_| self.unknown_unit())?;
+        Ok(build_energy(self.value, unit))
+    }
+
+    fn unknown_unit(&self) -> QuantityParseError {
+        QuantityParseError::UnknownUnit {
+            offset: self.unit_offset,
+            unit: self.unit.clone(),
+        }
+    }
+}
+
 fn calculate_energy(time: Time, length: Length) -> Result<Energy, DivideByZeroError> {
     let m = uom::si::f32::Mass::new::<kilogram>(1.67493e-27_f32);
 
@@ -61,27 +214,162 @@ fn calculate_energy(time: Time, length: Length) -> Result<Energy, DivideByZeroEr
     Ok(energy)
 }
 
+// Relativistic kinetic energy for flight velocities approaching c:
+// from `v = L/t` form the Lorentz factor `γ = 1/√(1 − (v/c)²)` and return
+// `E_kin = (γ − 1)·m·c²`, using the same neutron rest mass as `calculate_energy`.
+fn calculate_energy_relativistic(time: Time, length: Length) -> Result<Energy, DivideByZeroError> {
+    let m = 1.67493e-27_f32;
+
+    // Check if either time or length is zero
+    if time == Time::new::<second>(0.0) {
+        return Err(DivideByZeroError::TimeIsZero);
+    }
+    if length == Length::new::<meter>(0.0) {
+        return Err(DivideByZeroError::LengthIsZero);
+    }
+
+    let c = 299792458.0_f32;
+    let v = length.get::<meter>() / time.get::<second>();
+    if v >= c {
+        return Err(DivideByZeroError::FasterThanLight);
+    }
+
+    let beta = v / c;
+    let gamma = 1.0 / (1.0 - beta * beta).sqrt();
+    let energy_joules = (gamma - 1.0) * m * c * c;
+
+    Ok(Energy::new::<joule>(energy_joules))
+}
+
+// Solve for time-of-flight from a target energy: `t = L·√(m/(2E))`.
+fn calculate_time(energy: Energy, length: Length) -> Result<Time, DivideByZeroError> {
+    let m = 1.67493e-27_f32;
+
+    if energy == Energy::new::<joule>(0.0) {
+        return Err(DivideByZeroError::EnergyIsZero);
+    }
+
+    let time = length.get::<meter>() * (m / (2.0 * energy.get::<joule>())).sqrt();
+
+    Ok(Time::new::<second>(time))
+}
+
+// Solve for flight path length from a target energy: `L = t·√(2E/m)`.
+fn calculate_length(energy: Energy, time: Time) -> Result<Length, DivideByZeroError> {
+    let m = 1.67493e-27_f32;
+
+    let length = time.get::<second>() * (2.0 * energy.get::<joule>() / m).sqrt();
+
+    Ok(Length::new::<meter>(length))
+}
+
+// Flight velocity `v = L/t`.
+fn calculate_velocity(time: Time, length: Length) -> Result<Velocity, DivideByZeroError> {
+    if time == Time::new::<second>(0.0) {
+        return Err(DivideByZeroError::TimeIsZero);
+    }
+
+    Ok(length / time)
+}
+
+// de Broglie wavelength `λ = h/(m·v)`, using Planck's constant and the
+// neutron rest mass.
+fn calculate_wavelength(time: Time, length: Length) -> Result<Length, DivideByZeroError> {
+    let m = 1.67493e-27_f32;
+    let h = 6.62607015e-34_f32;
+
+    let velocity = calculate_velocity(time, length)?;
+    let v = velocity.get::<meter_per_second>();
+    if v == 0.0 {
+        return Err(DivideByZeroError::LengthIsZero);
+    }
+
+    Ok(Length::new::<meter>(h / (m * v)))
+}
+
+// Physical dimension of a known unit, used to validate that a parsed unit
+// matches the quantity it is being used for.
+#[derive(PartialEq, Clone, Copy)]
+enum Dimension {
+    Length,
+    Time,
+    Energy,
+}
+
+// One row of the unit registry: a canonical name, its aliases, the dimension
+// it belongs to, and the factor that scales a value in this unit to base SI
+// (meters, seconds, joules). Inspired by Cantera's `knownUnits` table — new
+// length and time units are added here and nowhere else; a new energy unit
+// also needs an `EnergyUnit` variant and its arms in `parse_energy_unit`,
+// `build_energy` and `format_energy`.
+struct UnitEntry {
+    canonical: &'static str,
+    aliases: &'static [&'static str],
+    dimension: Dimension,
+    scale: f32,
+}
+
+const KNOWN_UNITS: &[UnitEntry] = &[
+    // Length (base: meter)
+    UnitEntry { canonical: "angstrom", aliases: &["a", "angstrom", "angstroms"], dimension: Dimension::Length, scale: 1e-10 },
+    UnitEntry { canonical: "nanometer", aliases: &["nm", "nanometer", "nanometers"], dimension: Dimension::Length, scale: 1e-9 },
+    UnitEntry { canonical: "micron", aliases: &["um", "micron", "microns", "micrometer", "micrometers"], dimension: Dimension::Length, scale: 1e-6 },
+    UnitEntry { canonical: "centimeter", aliases: &["cm", "centimeter", "centimeters"], dimension: Dimension::Length, scale: 1e-2 },
+    UnitEntry { canonical: "meter", aliases: &["m", "meter", "meters"], dimension: Dimension::Length, scale: 1.0 },
+    UnitEntry { canonical: "kilometer", aliases: &["km", "kilometer", "kilometers"], dimension: Dimension::Length, scale: 1e3 },
+    // Time (base: second)
+    UnitEntry { canonical: "femtosecond", aliases: &["fs", "femtosecond", "femtoseconds"], dimension: Dimension::Time, scale: 1e-15 },
+    UnitEntry { canonical: "picosecond", aliases: &["ps", "picosecond", "picoseconds"], dimension: Dimension::Time, scale: 1e-12 },
+    UnitEntry { canonical: "nanosecond", aliases: &["ns", "nanosecond", "nanoseconds"], dimension: Dimension::Time, scale: 1e-9 },
+    UnitEntry { canonical: "microsecond", aliases: &["mus", "us", "microsecond", "microseconds"], dimension: Dimension::Time, scale: 1e-6 },
+    UnitEntry { canonical: "millisecond", aliases: &["ms", "millisecond", "milliseconds"], dimension: Dimension::Time, scale: 1e-3 },
+    UnitEntry { canonical: "second", aliases: &["s", "second", "seconds"], dimension: Dimension::Time, scale: 1.0 },
+    // Energy (base: joule)
+    UnitEntry { canonical: "ev", aliases: &["ev", "electronvolt", "electronvolts"], dimension: Dimension::Energy, scale: 1.602176634e-19 },
+    UnitEntry { canonical: "kev", aliases: &["kev", "kiloelectronvolt", "kiloelectronvolts"], dimension: Dimension::Energy, scale: 1.602176634e-16 },
+    UnitEntry { canonical: "mev", aliases: &["mev", "megaelectronvolt", "megaelectronvolts"], dimension: Dimension::Energy, scale: 1.602176634e-13 },
+    UnitEntry { canonical: "gev", aliases: &["gev", "gigaelectronvolt", "gigaelectronvolts"], dimension: Dimension::Energy, scale: 1.602176634e-10 },
+    UnitEntry { canonical: "j", aliases: &["j", "joule", "joules"], dimension: Dimension::Energy, scale: 1.0 },
+];
+
+// Format a base-SI value (meters, seconds, joules) in the requested registry
+// unit, so inverse solves honor `--unit` the way the forward energy path does.
+fn format_in_unit(
+    si_value: f32,
+    unit: &str,
+    dimension: Dimension,
+) -> Result<String, UnsupportedUnitError> {
+    let entry = lookup_unit(unit, dimension).ok_or_else(|| match dimension {
+        Dimension::Length => UnsupportedUnitError::Length(unit.to_string()),
+        Dimension::Time => UnsupportedUnitError::Time(unit.to_string()),
+        Dimension::Energy => UnsupportedUnitError::Energy(unit.to_string()),
+    })?;
+    Ok(format!("{} {}", si_value / entry.scale, entry.canonical))
+}
+
+// Look up a unit by any of its aliases, requiring it to match `dimension`.
+fn lookup_unit(unit: &str, dimension: Dimension) -> Option<&'static UnitEntry> {
+    let unit = unit.trim().to_lowercase();
+    KNOWN_UNITS
+        .iter()
+        .find(|entry| entry.dimension == dimension && entry.aliases.contains(&unit.as_str()))
+}
+
 // Functions to parse length and time inputs
 fn parse_length(quantity: f32, unit: &str) -> Result<Length, UnsupportedUnitError> {
-    match unit.trim().to_lowercase().as_str() {
-        "cm" | "centimeter" | "centimeters" => Ok(Length::new::<centimeter>(quantity)),
-        "m" | "meter" | "meters" => Ok(Length::new::<meter>(quantity)),
-        "km" | "kilometer" | "kilometers" => Ok(Length::new::<kilometer>(quantity)),
-        _ => Err(UnsupportedUnitError::Length(unit.to_string())),
-    }
+    let entry = lookup_unit(unit, Dimension::Length)
+        .ok_or_else(|| UnsupportedUnitError::Length(unit.to_string()))?;
+    Ok(Length::new::<meter>(quantity * entry.scale))
 }
 
 fn parse_time(quantity: f32, unit: &str) -> Result<Time, UnsupportedUnitError> {
-    match unit.trim().to_lowercase().as_str() {
-        "ns" | "nanosecond" | "nanoseconds" => Ok(Time::new::<nanosecond>(quantity)),
-        "mus" | "us" | "microsecond" | "microseconds" => Ok(Time::new::<microsecond>(quantity)),
-        "ms" | "millisecond" | "milliseconds" => Ok(Time::new::<millisecond>(quantity)),
-        "s" | "second" | "seconds" => Ok(Time::new::<second>(quantity)),
-        _ => Err(UnsupportedUnitError::Time(unit.to_string())),
-    }
+    let entry = lookup_unit(unit, Dimension::Time)
+        .ok_or_else(|| UnsupportedUnitError::Time(unit.to_string()))?;
+    Ok(Time::new::<second>(quantity * entry.scale))
 }
 
 // Enum for supported units
+#[derive(Clone, Copy)]
 enum EnergyUnit {
     Electronvolt,
     Kiloelectronvolt,
@@ -90,111 +378,398 @@ enum EnergyUnit {
     Joule,
 }
 
+// Enum for supported velocity output units
+enum VelocityUnit {
+    MeterPerSecond,
+    KilometerPerSecond,
+}
+
+// Enum for supported wavelength output units
+enum WavelengthUnit {
+    Angstrom,
+    Nanometer,
+    Meter,
+}
+
+// Function to parse velocity unit input
+fn parse_velocity_unit(unit: &str) -> Result<VelocityUnit, UnsupportedUnitError> {
+    match unit.trim().to_lowercase().as_str() {
+        "m/s" | "mps" | "meter_per_second" | "meters_per_second" => {
+            Ok(VelocityUnit::MeterPerSecond)
+        }
+        "km/s" | "kmps" | "kilometer_per_second" | "kilometers_per_second" => {
+            Ok(VelocityUnit::KilometerPerSecond)
+        }
+        _ => Err(UnsupportedUnitError::Velocity(unit.to_string())),
+    }
+}
+
+// Function to parse wavelength unit input
+fn parse_wavelength_unit(unit: &str) -> Result<WavelengthUnit, UnsupportedUnitError> {
+    match unit.trim().to_lowercase().as_str() {
+        "a" | "angstrom" | "angstroms" => Ok(WavelengthUnit::Angstrom),
+        "nm" | "nanometer" | "nanometers" => Ok(WavelengthUnit::Nanometer),
+        "m" | "meter" | "meters" => Ok(WavelengthUnit::Meter),
+        _ => Err(UnsupportedUnitError::Wavelength(unit.to_string())),
+    }
+}
+
+// Print a velocity in the requested output unit.
+fn print_velocity(velocity: Velocity, unit: VelocityUnit) {
+    match unit {
+        VelocityUnit::MeterPerSecond => println!(
+            "Velocity = {}",
+            velocity.into_format_args(meter_per_second, Abbreviation)
+        ),
+        VelocityUnit::KilometerPerSecond => println!(
+            "Velocity = {}",
+            velocity.into_format_args(kilometer_per_second, Abbreviation)
+        ),
+    }
+}
+
+// Print a wavelength in the requested output unit.
+fn print_wavelength(wavelength: Length, unit: WavelengthUnit) {
+    match unit {
+        WavelengthUnit::Angstrom => println!(
+            "Wavelength = {}",
+            wavelength.into_format_args(angstrom, Abbreviation)
+        ),
+        WavelengthUnit::Nanometer => println!(
+            "Wavelength = {}",
+            wavelength.into_format_args(nanometer, Abbreviation)
+        ),
+        WavelengthUnit::Meter => println!(
+            "Wavelength = {}",
+            wavelength.into_format_args(meter, Abbreviation)
+        ),
+    }
+}
+
+// Build an energy quantity from a magnitude and a supported energy unit.
+fn build_energy(quantity: f32, unit: EnergyUnit) -> Energy {
+    match unit {
+        EnergyUnit::Electronvolt => Energy::new::<electronvolt>(quantity),
+        EnergyUnit::Kiloelectronvolt => Energy::new::<kiloelectronvolt>(quantity),
+        EnergyUnit::Megaelectronvolt => Energy::new::<megaelectronvolt>(quantity),
+        EnergyUnit::Gigaelectronvolt => Energy::new::<gigaelectronvolt>(quantity),
+        EnergyUnit::Joule => Energy::new::<joule>(quantity),
+    }
+}
+
+// Format an energy in the requested output unit.
+fn format_energy(energy: Energy, unit: EnergyUnit) -> String {
+    match unit {
+        EnergyUnit::Electronvolt => {
+            format!("{}", energy.into_format_args(electronvolt, Abbreviation))
+        }
+        EnergyUnit::Kiloelectronvolt => {
+            format!("{}", energy.into_format_args(kiloelectronvolt, Abbreviation))
+        }
+        EnergyUnit::Megaelectronvolt => {
+            format!("{}", energy.into_format_args(megaelectronvolt, Abbreviation))
+        }
+        EnergyUnit::Gigaelectronvolt => {
+            format!("{}", energy.into_format_args(gigaelectronvolt, Abbreviation))
+        }
+        EnergyUnit::Joule => format!("{}", energy.into_format_args(joule, Abbreviation)),
+    }
+}
+
+// Print an energy in the requested output unit.
+fn print_energy(energy: Energy, unit: EnergyUnit) {
+    println!("Energy = {}", format_energy(energy, unit));
+}
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Flight path length from source to target in units of (cm, m, km).
-    #[arg(required = true, short, long, num_args(2), value_names = ["LENGTH","UNIT"])]
+    /// Flight path length from source to target, e.g. `10cm` or `10 cm`.
+    #[arg(short, long, num_args(1..=2), value_names = ["LENGTH","UNIT"])]
     length_of_flight_path: Vec<String>,
 
-    /// Time-of-Flight to convert to neutron Energy in units of (ns, us, ms, s).
-    #[arg(required = true, short, long, num_args(2), value_names = ["TIME","UNIT"])]
+    /// Time-of-Flight to convert to neutron Energy, e.g. `250ns` or `250 ns`.
+    #[arg(short, long, num_args(1..=2), value_names = ["TIME","UNIT"])]
     time_of_flight: Vec<String>,
 
-    /// Desired neutron energy units of (eV, KeE, MeV, J).
-    #[arg(short, long, num_args(1), default_value = "eV")]
+    /// Target neutron energy for inverse solving, e.g. `25eV` or `25 eV`.
+    /// Only eV, keV, MeV, GeV, and J are accepted.
+    #[arg(short, long, num_args(1..=2), value_names = ["ENERGY","UNIT"])]
+    energy: Vec<String>,
+
+    /// Desired output units. Defaults per `--output`: eV, m/s, or angstrom;
+    /// with `--solve time` or `--solve length`: s or m.
+    #[arg(short, long, num_args(1))]
     unit: Option<String>,
+
+    /// What to report: `energy` (default), `velocity`, or `wavelength`.
+    #[arg(short, long, default_value = "energy")]
+    output: Option<String>,
+
+    /// Which quantity to solve for: `energy` (default), `time`, or `length`.
+    #[arg(long, default_value = "energy")]
+    solve: Option<String>,
+
+    /// Auto-select the energy output unit by magnitude instead of `--unit`.
+    #[arg(long)]
+    auto: bool,
+
+    /// Batch mode: read rows of `length_value,length_unit,time_value,time_unit`
+    /// from a CSV file (or `-` for stdin) and print one energy per row.
+    /// Rows are computed classically and printed in `--unit`; `--relativistic`
+    /// and `--auto` are not applied in this mode.
+    #[arg(short, long)]
+    batch: Option<String>,
+
+    /// Compute kinetic energy relativistically instead of classically.
+    #[arg(long)]
+    relativistic: bool,
 }
 
 // Function to parse energy unit input
 fn parse_energy_unit(unit: &str) -> Result<EnergyUnit, UnsupportedUnitError> {
-    match unit.trim().to_lowercase().as_str() {
-        "ev" | "electronvolt" | "electronvolts" => Ok(EnergyUnit::Electronvolt),
-        "kev" | "kiloelectronvolt" | "kiloelectronvolts" => Ok(EnergyUnit::Kiloelectronvolt),
-        "mev" | "megaelectronvolt" | "megaelectronvolts" => Ok(EnergyUnit::Megaelectronvolt),
-        "gev" | "gigaelectronvolt" | "gigaelectronvolts" => Ok(EnergyUnit::Gigaelectronvolt),
-        "j" | "joule" | "joules" => Ok(EnergyUnit::Joule),
+    let entry = lookup_unit(unit, Dimension::Energy)
+        .ok_or_else(|| UnsupportedUnitError::Energy(unit.to_string()))?;
+    match entry.canonical {
+        "ev" => Ok(EnergyUnit::Electronvolt),
+        "kev" => Ok(EnergyUnit::Kiloelectronvolt),
+        "mev" => Ok(EnergyUnit::Megaelectronvolt),
+        "gev" => Ok(EnergyUnit::Gigaelectronvolt),
+        "j" => Ok(EnergyUnit::Joule),
         _ => Err(UnsupportedUnitError::Energy(unit.to_string())),
     }
 }
 
-fn main() {
-    let cli = Cli::parse();
+// Pick the energy unit whose magnitude keeps the displayed number in a
+// readable range, preferring eV/keV/MeV/GeV by order of magnitude.
+fn auto_energy_unit(energy: Energy) -> EnergyUnit {
+    let ev = energy.get::<electronvolt>().abs();
+    if ev >= 1e9 {
+        EnergyUnit::Gigaelectronvolt
+    } else if ev >= 1e6 {
+        EnergyUnit::Megaelectronvolt
+    } else if ev >= 1e3 {
+        EnergyUnit::Kiloelectronvolt
+    } else {
+        EnergyUnit::Electronvolt
+    }
+}
 
-    // Parse length value
-    let input_length_value: f32 = match cli.length_of_flight_path[0].parse() {
-        Ok(value) => value,
-        Err(err) => {
-            eprintln!("Error: parsing length value: {}", err);
-            return;
-        }
+// Resolve a required quantity argument, exiting with an error if it is
+// missing or malformed.
+fn required_quantity(args: &[String], label: &str) -> Quantity {
+    if args.is_empty() {
+        eprintln!("Error: --{} is required for this solve mode", label);
+        std::process::exit(1);
+    }
+    Quantity::from_args(args).unwrap_or_else(|err| {
+        eprintln!("Error: parsing {} value: {}", label, err);
+        std::process::exit(1);
+    })
+}
+
+// Resolve a quantity through one of its dimension tables, exiting on error.
+fn resolve<T>(result: Result<T, QuantityParseError>) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    })
+}
+
+// Default (length, time) -> energy path, honoring `--relativistic` and `--unit`.
+fn solve_energy(cli: &Cli) {
+    let length = resolve(required_quantity(&cli.length_of_flight_path, "length").length());
+    let time = resolve(required_quantity(&cli.time_of_flight, "time").time());
+
+    let energy_result = if cli.relativistic {
+        calculate_energy_relativistic(time, length)
+    } else {
+        calculate_energy(time, length)
     };
 
-    // Parse time value
-    let input_time_value: f32 = match cli.time_of_flight[0].parse() {
-        Ok(value) => value,
-        Err(err) => {
-            eprintln!("Error: parsing time value: {}", err);
-            return;
-        }
+    let energy = energy_result.unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    });
+
+    let energy_unit = if cli.auto {
+        auto_energy_unit(energy)
+    } else {
+        let output_unit = cli.unit.clone().unwrap_or_else(|| "eV".to_string());
+        parse_energy_unit(output_unit.trim()).unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        })
     };
 
-    let input_length_unit: &str = &cli.length_of_flight_path[1].trim().to_lowercase();
+    print_energy(energy, energy_unit);
+}
+
+// Forward (length, time) -> velocity path.
+fn solve_velocity(cli: &Cli) {
+    let length = resolve(required_quantity(&cli.length_of_flight_path, "length").length());
+    let time = resolve(required_quantity(&cli.time_of_flight, "time").time());
+
+    let velocity = calculate_velocity(time, length).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    });
+
+    let output_unit = cli.unit.clone().unwrap_or_else(|| "m/s".to_string());
+    let velocity_unit = parse_velocity_unit(output_unit.trim()).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    });
+
+    print_velocity(velocity, velocity_unit);
+}
+
+// Forward (length, time) -> de Broglie wavelength path.
+fn solve_wavelength(cli: &Cli) {
+    let length = resolve(required_quantity(&cli.length_of_flight_path, "length").length());
+    let time = resolve(required_quantity(&cli.time_of_flight, "time").time());
 
-    let input_time_unit: &str = &cli.time_of_flight[1].trim().to_lowercase();
+    let wavelength = calculate_wavelength(time, length).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    });
 
-    let length_quantity =
-        parse_length(input_length_value, input_length_unit).unwrap_or_else(|err| {
+    let output_unit = cli.unit.clone().unwrap_or_else(|| "angstrom".to_string());
+    let wavelength_unit = parse_wavelength_unit(output_unit.trim()).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    });
+
+    print_wavelength(wavelength, wavelength_unit);
+}
+
+// Inverse (length, energy) -> time path.
+fn solve_time(cli: &Cli) {
+    let length = resolve(required_quantity(&cli.length_of_flight_path, "length").length());
+    let energy = resolve(required_quantity(&cli.energy, "energy").energy());
+
+    let time = calculate_time(energy, length).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    });
+
+    let output_unit = cli.unit.clone().unwrap_or_else(|| "s".to_string());
+    let cell = format_in_unit(time.get::<second>(), output_unit.trim(), Dimension::Time)
+        .unwrap_or_else(|err| {
             eprintln!("Error: {}", err);
             std::process::exit(1);
         });
 
-    let time_quantity = parse_time(input_time_value, input_time_unit).unwrap_or_else(|err| {
+    println!("Time = {}", cell);
+}
+
+// Inverse (time, energy) -> length path.
+fn solve_length(cli: &Cli) {
+    let time = resolve(required_quantity(&cli.time_of_flight, "time").time());
+    let energy = resolve(required_quantity(&cli.energy, "energy").energy());
+
+    let length = calculate_length(energy, time).unwrap_or_else(|err| {
         eprintln!("Error: {}", err);
         std::process::exit(1);
     });
 
-    let output_energy_unit: &str = &cli.unit.unwrap().trim().to_lowercase();
-
-    let energy_quantity: Energy =
-        calculate_energy(time_quantity, length_quantity).unwrap_or_else(|err| {
+    let output_unit = cli.unit.clone().unwrap_or_else(|| "m".to_string());
+    let cell = format_in_unit(length.get::<meter>(), output_unit.trim(), Dimension::Length)
+        .unwrap_or_else(|err| {
             eprintln!("Error: {}", err);
             std::process::exit(1);
         });
 
-    let energy_unit = parse_energy_unit(output_energy_unit).unwrap_or_else(|err| {
+    println!("Length = {}", cell);
+}
+
+// Convert one CSV row into a formatted energy cell, surfacing the offending
+// field in the error so a single bad row can be reported and skipped.
+fn process_row(line: &str, unit: EnergyUnit) -> Result<String, String> {
+    let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+    if fields.len() != 4 {
+        return Err(format!(
+            "expected 4 fields (length_value,length_unit,time_value,time_unit), found {}",
+            fields.len()
+        ));
+    }
+
+    let length_value: f32 = fields[0]
+        .parse()
+        .map_err(|_| format!("invalid length value \"{}\"", fields[0]))?;
+    let time_value: f32 = fields[2]
+        .parse()
+        .map_err(|_| format!("invalid time value \"{}\"", fields[2]))?;
+
+    let length = parse_length(length_value, fields[1]).map_err(|err| err.to_string())?;
+    let time = parse_time(time_value, fields[3]).map_err(|err| err.to_string())?;
+    let energy = calculate_energy(time, length).map_err(|err| err.to_string())?;
+
+    Ok(format_energy(energy, unit))
+}
+
+// Batch (CSV) -> one energy per row. Per-row failures are reported with their
+// line number and offending field without aborting the whole run.
+fn run_batch(cli: &Cli, source: &str) {
+    let output_unit = cli.unit.clone().unwrap_or_else(|| "eV".to_string());
+    let energy_unit = parse_energy_unit(output_unit.trim()).unwrap_or_else(|err| {
         eprintln!("Error: {}", err);
         std::process::exit(1);
     });
 
-    match energy_unit {
-        EnergyUnit::Electronvolt => {
-            println!(
-                "Energy = {}",
-                energy_quantity.into_format_args(electronvolt, Abbreviation)
-            )
+    let contents = if source == "-" {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer).unwrap_or_else(|err| {
+            eprintln!("Error: reading stdin: {}", err);
+            std::process::exit(1);
+        });
+        buffer
+    } else {
+        std::fs::read_to_string(source).unwrap_or_else(|err| {
+            eprintln!("Error: reading {}: {}", source, err);
+            std::process::exit(1);
+        })
+    };
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
         }
-        EnergyUnit::Kiloelectronvolt => {
-            println!(
-                "Energy = {}",
-                energy_quantity.into_format_args(kiloelectronvolt, Abbreviation)
-            )
+        match process_row(line, energy_unit) {
+            Ok(cell) => println!("{}", cell),
+            Err(err) => eprintln!("line {}: {}", line_number, err),
         }
-        EnergyUnit::Megaelectronvolt => {
-            println!(
-                "Energy = {}",
-                energy_quantity.into_format_args(megaelectronvolt, Abbreviation)
-            )
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    // Batch mode short-circuits the single-calculation paths.
+    if let Some(source) = cli.batch.clone() {
+        run_batch(&cli, source.trim());
+        return;
+    }
+
+    // `--output` selects the reported quantity; `--solve` only applies to the
+    // default energy output, where it can instead back out time or length.
+    match cli.output.clone().unwrap().trim().to_lowercase().as_str() {
+        "energy" => match cli.solve.clone().unwrap().trim().to_lowercase().as_str() {
+            "energy" => solve_energy(&cli),
+            "time" => solve_time(&cli),
+            "length" => solve_length(&cli),
+            other => {
+                eprintln!("Error: unknown solve target: {}", other);
+                std::process::exit(1);
+            }
+        },
+        "velocity" => solve_velocity(&cli),
+        "wavelength" => solve_wavelength(&cli),
+        other => {
+            eprintln!("Error: unknown output mode: {}", other);
+            std::process::exit(1);
         }
-        EnergyUnit::Gigaelectronvolt => {
-            println!(
-                "Energy = {}",
-                energy_quantity.into_format_args(gigaelectronvolt, Abbreviation)
-            )
-        }
-        EnergyUnit::Joule => println!(
-            "Energy = {}",
-            energy_quantity.into_format_args(joule, Abbreviation)
-        ),
     }
 }